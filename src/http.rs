@@ -1,9 +1,9 @@
 use std::{
     fmt::Display,
     fs,
-    io::{Error, ErrorKind},
+    io::{Error, ErrorKind, Read},
     path::Path,
-    str::FromStr,
+    str::{from_utf8, FromStr},
 };
 
 use crate::error::{Result, WebServerError};
@@ -58,15 +58,37 @@ impl ContentType {
             filename
         )))
     }
+
+    /// Parse a `Content-Type` field value such as `text/html; charset=utf-8`.
+    /// Returns `None` for a media type outside the known set.
+    fn from_wire(value: &str) -> Option<Self> {
+        let mut parts = value.split(';');
+        let media = parts.next()?.trim();
+        let charset = parts.find_map(|param| {
+            param
+                .trim()
+                .strip_prefix("charset=")
+                .map(|c| match c.trim().to_ascii_lowercase().as_str() {
+                    "utf-8" => Some(Charset::Utf8),
+                    _ => None,
+                })
+        });
+        match media {
+            "text/html" => Some(Self::TextHTML(charset.flatten())),
+            "text/javascript" => Some(Self::TextJavascript(charset.flatten())),
+            "application/wasm" => Some(Self::ApplicationWASM),
+            _ => None,
+        }
+    }
 }
 
 impl Display for ContentType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::TextHTML(None) => write!(f, "text/html"),
-            Self::TextHTML(Some(c)) => write!(f, "text/html charset:{}", c),
+            Self::TextHTML(Some(c)) => write!(f, "text/html; charset={}", c),
             Self::TextJavascript(None) => write!(f, "text/javascript"),
-            Self::TextJavascript(Some(c)) => write!(f, "text/javascript charset:{}", c),
+            Self::TextJavascript(Some(c)) => write!(f, "text/javascript; charset={}", c),
             Self::ApplicationWASM => write!(f, "application/wasm"),
         }
     }
@@ -77,6 +99,9 @@ pub enum Header {
     Host(String),
     ContentLength(usize),
     ContentType(ContentType),
+    Connection(String),
+    Accept(String),
+    UserAgent(String),
     Other(String, String),
 }
 
@@ -90,10 +115,22 @@ impl FromStr for Header {
             line.len(),
         )))?;
         let contents = &contents[1..]; // drop space
-        match header {
-            "Host" => Ok(Header::Host(String::from(contents))),
-            _ => Ok(Header::Other(String::from(header), String::from(contents))),
-        }
+        let other = || Header::Other(String::from(header), String::from(contents));
+        Ok(match header {
+            "Host" => Header::Host(String::from(contents)),
+            "Content-Length" => match contents.trim().parse() {
+                Ok(length) => Header::ContentLength(length),
+                Err(_) => other(),
+            },
+            "Content-Type" => match ContentType::from_wire(contents.trim()) {
+                Some(content_type) => Header::ContentType(content_type),
+                None => other(),
+            },
+            "Connection" => Header::Connection(String::from(contents.trim())),
+            "Accept" => Header::Accept(String::from(contents.trim())),
+            "User-Agent" => Header::UserAgent(String::from(contents)),
+            _ => other(),
+        })
     }
 }
 
@@ -103,20 +140,220 @@ impl Display for Header {
             Self::Host(s) => write!(f, "Host: {}", s),
             Self::ContentLength(n) => write!(f, "Content-Length: {}", n),
             Self::ContentType(c) => write!(f, "Content-Type: {}", c),
+            Self::Connection(s) => write!(f, "Connection: {}", s),
+            Self::Accept(s) => write!(f, "Accept: {}", s),
+            Self::UserAgent(s) => write!(f, "User-Agent: {}", s),
             Self::Other(s, t) => write!(f, "{}: {}", s, t),
         }
     }
 }
 
+/// Encode an integer as a QUIC variable-length integer (RFC 9000 §16): the
+/// top two bits of the first byte select a 1/2/4/8-byte big-endian length.
+fn encode_varint(value: u64) -> Vec<u8> {
+    if value < (1 << 6) {
+        vec![value as u8]
+    } else if value < (1 << 14) {
+        (value as u16 | 0x4000).to_be_bytes().to_vec()
+    } else if value < (1 << 30) {
+        (value as u32 | 0x8000_0000).to_be_bytes().to_vec()
+    } else {
+        (value | 0xC000_0000_0000_0000).to_be_bytes().to_vec()
+    }
+}
+
+/// Read a QUIC varint from `buf` at `pos`, advancing `pos`. Rejects a varint
+/// whose declared length runs past the end of the buffer.
+fn read_varint(buf: &[u8], pos: &mut usize) -> Result<u64> {
+    let first = *buf
+        .get(*pos)
+        .ok_or(parse_error(format!("Truncated varint")))?;
+    let length = 1usize << (first >> 6);
+    if *pos + length > buf.len() {
+        return Result::Err(parse_error(format!("Truncated varint")));
+    }
+    let mut value = (first & 0x3f) as u64;
+    for i in 1..length {
+        value = (value << 8) | buf[*pos + i] as u64;
+    }
+    *pos += length;
+    Ok(value)
+}
+
+/// Append a varint-length-prefixed byte string.
+fn encode_byte_string(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(&encode_varint(bytes.len() as u64));
+    out.extend_from_slice(bytes);
+}
+
+/// Read a varint-length-prefixed byte string, rejecting a truncated body.
+fn read_byte_string(buf: &[u8], pos: &mut usize) -> Result<Vec<u8>> {
+    let len = read_varint(buf, pos)? as usize;
+    if *pos + len > buf.len() {
+        return Result::Err(parse_error(format!("Truncated byte string")));
+    }
+    let bytes = buf[*pos..*pos + len].to_vec();
+    *pos += len;
+    Ok(bytes)
+}
+
+/// The wire `(name, value)` pair for a header, used by the binary encoding.
+fn header_name_value(header: &Header) -> (String, String) {
+    match header {
+        Header::Host(s) => (String::from("Host"), s.clone()),
+        Header::ContentLength(n) => (String::from("Content-Length"), n.to_string()),
+        Header::ContentType(c) => (String::from("Content-Type"), c.to_string()),
+        Header::Connection(s) => (String::from("Connection"), s.clone()),
+        Header::Accept(s) => (String::from("Accept"), s.clone()),
+        Header::UserAgent(s) => (String::from("User-Agent"), s.clone()),
+        Header::Other(name, value) => (name.clone(), value.clone()),
+    }
+}
+
+/// Encode a header field section: a varint giving the section's total byte
+/// length followed by the varint-length-prefixed `(name, value)` pairs.
+fn encode_field_section(headers: &[Header]) -> Vec<u8> {
+    let mut inner = Vec::new();
+    for header in headers {
+        let (name, value) = header_name_value(header);
+        encode_byte_string(&mut inner, name.as_bytes());
+        encode_byte_string(&mut inner, value.as_bytes());
+    }
+    let mut out = encode_varint(inner.len() as u64);
+    out.extend_from_slice(&inner);
+    out
+}
+
+/// Decode a field section, enforcing that the consumed byte count exactly
+/// matches the length the section declared.
+fn read_field_section(buf: &[u8], pos: &mut usize) -> Result<Vec<Header>> {
+    let section_len = read_varint(buf, pos)? as usize;
+    let end = *pos + section_len;
+    if end > buf.len() {
+        return Result::Err(parse_error(format!("Truncated field section")));
+    }
+    let mut headers = Vec::new();
+    while *pos < end {
+        let name = read_byte_string(buf, pos)?;
+        let value = read_byte_string(buf, pos)?;
+        if *pos > end {
+            return Result::Err(parse_error(format!("Field pair overruns field section")));
+        }
+        headers.push(Header::from_str(&format!(
+            "{}: {}",
+            from_utf8(&name)?,
+            from_utf8(&value)?
+        ))?);
+    }
+    if *pos != end {
+        return Result::Err(parse_error(format!(
+            "Field section length did not match bytes consumed"
+        )));
+    }
+    Ok(headers)
+}
+
 #[derive(Clone, Debug)]
 pub enum RequestMethod {
     Head,
     Get,
-    Post(String),
+    Post(Vec<u8>),
+}
+
+/// A request URI split into its decoded path and parsed query parameters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParsedUri {
+    pub path: String,
+    pub query: Vec<(String, String)>,
+}
+
+/// Decode percent-escapes in `input`. When `plus_as_space` is set, `+` also
+/// decodes to a space, as required for query strings and form bodies.
+pub(crate) fn percent_decode(input: &str, plus_as_space: bool) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            // Hex-parse the two raw bytes directly instead of re-slicing
+            // `input` as a `&str`: `i + 1`/`i + 3` are byte offsets computed
+            // from the `%` position and may fall inside a multi-byte UTF-8
+            // character, which would panic on a non-char-boundary slice.
+            b'%' if i + 3 <= bytes.len() => {
+                match std::str::from_utf8(&bytes[i + 1..i + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 3;
+                    }
+                    None => {
+                        out.push(b'%');
+                        i += 1;
+                    }
+                }
+            }
+            b'+' if plus_as_space => {
+                out.push(b' ');
+                i += 1;
+            }
+            byte => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Parse an `application/x-www-form-urlencoded` string into key/value pairs,
+/// preserving repeated keys and empty values.
+pub(crate) fn parse_urlencoded(input: &str) -> Vec<(String, String)> {
+    input
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => (percent_decode(key, true), percent_decode(value, true)),
+            None => (percent_decode(pair, true), String::new()),
+        })
+        .collect()
+}
+
+/// Upper bound on a request body we are willing to buffer, used by
+/// [`Request::parse_stream`] when the caller does not specify its own.
+pub const DEFAULT_MAX_BODY_SIZE: usize = 64 * 1024 * 1024;
+
+const READ_CHUNK_SIZE: usize = 8192;
+
+/// Upper bound on the header block (request line + headers, before the
+/// `\r\n\r\n` terminator) we are willing to buffer while scanning for it, so
+/// a client that never sends a blank line can't grow the buffer unbounded.
+const MAX_HEADER_SIZE: usize = 64 * 1024;
+
+/// Locate the end of the header block (the first `\r\n\r\n`) in `buffer`,
+/// returning the index of the byte following the terminator.
+fn find_header_terminator(buffer: &[u8]) -> Option<usize> {
+    buffer
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map(|pos| pos + 4)
+}
+
+/// Extract a `Content-Length` value from already-parsed headers, tolerating
+/// both the typed variant and the catch-all `Other` form.
+fn content_length_of(headers: &[Header]) -> Option<usize> {
+    headers.iter().find_map(|h| match h {
+        Header::ContentLength(n) => Some(*n),
+        Header::Other(name, value) if name.eq_ignore_ascii_case("Content-Length") => {
+            value.trim().parse().ok()
+        }
+        _ => None,
+    })
 }
 
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Request {
     uri: Uri,
     http_version: HttpVersion,
@@ -125,22 +362,6 @@ pub struct Request {
 }
 
 impl Request {
-    fn parse_request_headers_and_data(request: Vec<&str>) -> Result<(Vec<Header>, Option<String>)> {
-        let mut current_line = 1_usize; // skip first line
-        let mut headers = Vec::new();
-        while current_line < request.len()
-            && request[current_line] != "\r\n"
-            && request[current_line] != ""
-        {
-            headers.push(Header::from_str(request[current_line])?);
-            current_line += 1;
-        }
-
-        let data = request.get(current_line + 1..).map(|v| v.concat());
-
-        Ok((headers, data))
-    }
-
     pub fn raw(uri: &str, http_version: &str, headers: &[Header], method: RequestMethod) -> Self {
         Request {
             uri: String::from(uri),
@@ -154,6 +375,37 @@ impl Request {
         &self.uri
     }
 
+    /// The URI split into a decoded path and parsed query parameters.
+    pub fn parsed_uri(&self) -> ParsedUri {
+        match self.uri.split_once('?') {
+            Some((path, query)) => ParsedUri {
+                path: percent_decode(path, false),
+                query: parse_urlencoded(query),
+            },
+            None => ParsedUri {
+                path: percent_decode(&self.uri, false),
+                query: Vec::new(),
+            },
+        }
+    }
+
+    /// The query parameters from the URI, with repeated keys preserved.
+    pub fn query(&self) -> Vec<(String, String)> {
+        self.parsed_uri().query
+    }
+
+    /// Decode an `application/x-www-form-urlencoded` POST body into key/value
+    /// pairs; empty for non-POST or non-UTF-8 bodies.
+    pub fn form_data(&self) -> Vec<(String, String)> {
+        match &self.method {
+            RequestMethod::Post(body) => match from_utf8(body) {
+                Ok(text) => parse_urlencoded(text),
+                Err(_) => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
     pub fn http_version(&self) -> &str {
         &self.http_version
     }
@@ -162,35 +414,133 @@ impl Request {
         &self.headers
     }
 
+    /// Look up a request header by its field name, case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&Header> {
+        self.headers
+            .iter()
+            .find(|header| header_name_value(header).0.eq_ignore_ascii_case(name))
+    }
+
+    /// The parsed `Content-Length`, if the client sent a valid one. Falls
+    /// back to a case-insensitively-named `Header::Other("Content-Length",
+    /// ...)`, since header field names are case-insensitive per HTTP but
+    /// [`Header::from_str`]'s typed matching is not.
+    pub fn content_length(&self) -> Option<usize> {
+        content_length_of(&self.headers)
+    }
+
     pub fn request_method(&self) -> &RequestMethod {
         &self.method
     }
+
+    /// A cheap copy of this request with any POST body discarded. Used by
+    /// code paths (such as [`PostHook`](crate::PostHook)) that need the
+    /// request's metadata but must not pay to duplicate a large body.
+    pub fn without_body(&self) -> Self {
+        let method = match &self.method {
+            RequestMethod::Post(_) => RequestMethod::Post(Vec::new()),
+            other => other.clone(),
+        };
+        Request {
+            uri: self.uri.clone(),
+            http_version: self.http_version.clone(),
+            headers: self.headers.clone(),
+            method,
+        }
+    }
 }
 impl Request {
-    pub fn parse(request: &str) -> Result<Self> {
-        let request_lines: Vec<&str> = request.split("\r\n").collect();
+    /// Parse a request incrementally off a live stream.
+    ///
+    /// Headers are read line-by-line until the blank `\r\n\r\n`, then exactly
+    /// `Content-Length` body bytes are read (looping on `read` until
+    /// satisfied) and stored verbatim, so the body may be binary and larger
+    /// than any single read. Uses [`DEFAULT_MAX_BODY_SIZE`] as the body cap.
+    pub fn parse_stream(stream: &mut impl Read) -> Result<Self> {
+        Self::parse_stream_with_limit(stream, DEFAULT_MAX_BODY_SIZE)
+    }
+
+    /// Like [`Request::parse_stream`] but rejects bodies whose declared
+    /// `Content-Length` exceeds `max_body_size`, bounding memory use.
+    pub fn parse_stream_with_limit(stream: &mut impl Read, max_body_size: usize) -> Result<Self> {
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut chunk = [0u8; READ_CHUNK_SIZE];
+
+        // Accumulate bytes until the header terminator is seen, bailing out
+        // if a client streams headers forever without ever sending one.
+        let header_end = loop {
+            if let Some(end) = find_header_terminator(&buffer) {
+                break end;
+            }
+            if buffer.len() > MAX_HEADER_SIZE {
+                return Result::Err(parse_error(format!(
+                    "Header block exceeds maximum of {} bytes",
+                    MAX_HEADER_SIZE
+                )));
+            }
+            let read = stream.read(&mut chunk)?;
+            if read == 0 {
+                return Result::Err(parse_error(format!(
+                    "Client closed connection before headers were complete"
+                )));
+            }
+            buffer.extend_from_slice(&chunk[..read]);
+        };
+
+        // Everything before the terminator is the textual head.
+        let head = from_utf8(&buffer[..header_end])?;
+        let head_lines: Vec<&str> = head.split("\r\n").collect();
 
-        let first_line: Vec<&str> = request_lines
+        let first_line: Vec<&str> = head_lines
             .get(0)
             .ok_or(parse_error(format!("Request empty")))?
             .split(" ")
             .collect();
 
         if first_line.len() != 3 {
-            return Result::Err(parse_error(format!("Malformed request: {}", request)));
+            return Result::Err(parse_error(format!("Malformed request: {}", head)));
         }
 
         let uri: Uri = String::from(first_line[1]);
         let http_version: HttpVersion = String::from(first_line[2]);
 
-        let (headers, data) = Request::parse_request_headers_and_data(request_lines)?;
+        let mut headers = Vec::new();
+        for line in &head_lines[1..] {
+            if line.is_empty() {
+                continue;
+            }
+            headers.push(Header::from_str(line)?);
+        }
+
+        let content_length = content_length_of(&headers).unwrap_or(0);
+        if content_length > max_body_size {
+            return Result::Err(parse_error(format!(
+                "Declared Content-Length {} exceeds maximum of {} bytes",
+                content_length, max_body_size
+            )));
+        }
+
+        // Carry over any body bytes already read while scanning for headers,
+        // then read until `Content-Length` is satisfied.
+        let mut body: Vec<u8> = buffer[header_end..].to_vec();
+        body.truncate(content_length);
+        while body.len() < content_length {
+            let read = stream.read(&mut chunk)?;
+            if read == 0 {
+                return Result::Err(parse_error(format!(
+                    "Client closed connection with {} of {} body bytes received",
+                    body.len(),
+                    content_length
+                )));
+            }
+            let remaining = content_length - body.len();
+            body.extend_from_slice(&chunk[..read.min(remaining)]);
+        }
 
         let method = match first_line[0] {
             "GET" => Ok(RequestMethod::Get),
-            "POST" => {
-                let data = data.ok_or(parse_error(format!("Post missing data")))?;
-                Ok(RequestMethod::Post(data))
-            }
+            "HEAD" => Ok(RequestMethod::Head),
+            "POST" => Ok(RequestMethod::Post(body)),
             _ => Result::Err(parse_error(format!(
                 "Failed to match request type with: {}",
                 first_line[0]
@@ -206,48 +556,168 @@ impl Request {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ResponseType {
-    Ok,
-    NotFound,
-    Forbidden,
-    MethodNotAllowed,
-    InternalServerError,
-}
+impl Request {
+    /// Encode this request in the RFC 9292 known-length binary format, so it
+    /// can traverse Oblivious HTTP relays and other binary-HTTP
+    /// intermediaries.
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&encode_varint(0)); // framing: known-length request
 
-impl ResponseType {
-    fn condition_code(&self) -> usize {
-        match self {
-            Self::Ok => 200,
-            Self::Forbidden => 403,
-            Self::NotFound => 404,
-            Self::MethodNotAllowed => 405,
-            Self::InternalServerError => 500,
+        let method = match self.method {
+            RequestMethod::Head => "HEAD",
+            RequestMethod::Get => "GET",
+            RequestMethod::Post(_) => "POST",
+        };
+        let authority = self
+            .headers
+            .iter()
+            .find_map(|h| match h {
+                Header::Host(s) => Some(s.clone()),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        encode_byte_string(&mut out, method.as_bytes());
+        encode_byte_string(&mut out, b"https");
+        encode_byte_string(&mut out, authority.as_bytes());
+        encode_byte_string(&mut out, self.uri.as_bytes());
+
+        out.extend_from_slice(&encode_field_section(&self.headers));
+
+        let content: &[u8] = match &self.method {
+            RequestMethod::Post(body) => body,
+            _ => &[],
+        };
+        encode_byte_string(&mut out, content);
+
+        out.extend_from_slice(&encode_field_section(&[])); // empty trailers
+        out
+    }
+
+    /// Decode an RFC 9292 known-length binary request.
+    pub fn parse_binary(buf: &[u8]) -> Result<Self> {
+        let mut pos = 0;
+        if read_varint(buf, &mut pos)? != 0 {
+            return Result::Err(parse_error(format!(
+                "Not a known-length binary request (wrong framing indicator)"
+            )));
         }
+
+        let method = read_byte_string(buf, &mut pos)?;
+        let _scheme = read_byte_string(buf, &mut pos)?;
+        let _authority = read_byte_string(buf, &mut pos)?;
+        let path = read_byte_string(buf, &mut pos)?;
+
+        let headers = read_field_section(buf, &mut pos)?;
+        let content = read_byte_string(buf, &mut pos)?;
+        let _trailers = read_field_section(buf, &mut pos)?;
+
+        let method = match from_utf8(&method)? {
+            "GET" => Ok(RequestMethod::Get),
+            "HEAD" => Ok(RequestMethod::Head),
+            "POST" => Ok(RequestMethod::Post(content)),
+            other => Result::Err(parse_error(format!(
+                "Failed to match request type with: {}",
+                other
+            ))),
+        }?;
+
+        Ok(Request {
+            uri: String::from(from_utf8(&path)?),
+            http_version: String::from(HTTP_VERSION),
+            headers,
+            method,
+        })
     }
+}
 
-    fn name(&self) -> &str {
-        match self {
-            Self::Ok => "Ok",
-            Self::Forbidden => "Forbidden",
-            Self::NotFound => "Not Found",
-            Self::MethodNotAllowed => "Method Not Allowed",
-            Self::InternalServerError => "Internal Server Error",
+/// An HTTP status code and its registry of standard reason phrases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusCode(pub u16);
+
+impl StatusCode {
+    pub fn ok() -> Self {
+        Self(200)
+    }
+    pub fn created() -> Self {
+        Self(201)
+    }
+    pub fn no_content() -> Self {
+        Self(204)
+    }
+    pub fn partial_content() -> Self {
+        Self(206)
+    }
+    pub fn not_modified() -> Self {
+        Self(304)
+    }
+    pub fn bad_request() -> Self {
+        Self(400)
+    }
+    pub fn forbidden() -> Self {
+        Self(403)
+    }
+    pub fn not_found() -> Self {
+        Self(404)
+    }
+    pub fn method_not_allowed() -> Self {
+        Self(405)
+    }
+    pub fn internal_server_error() -> Self {
+        Self(500)
+    }
+
+    pub fn code(&self) -> u16 {
+        self.0
+    }
+
+    /// The reason phrase registered for this code, or `"Unknown"` for codes
+    /// outside the standard registry.
+    pub fn default_reason_phrase(&self) -> &'static str {
+        match self.0 {
+            100 => "Continue",
+            101 => "Switching Protocols",
+            200 => "OK",
+            201 => "Created",
+            202 => "Accepted",
+            204 => "No Content",
+            206 => "Partial Content",
+            301 => "Moved Permanently",
+            302 => "Found",
+            303 => "See Other",
+            304 => "Not Modified",
+            307 => "Temporary Redirect",
+            308 => "Permanent Redirect",
+            400 => "Bad Request",
+            401 => "Unauthorized",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            406 => "Not Acceptable",
+            408 => "Request Timeout",
+            411 => "Length Required",
+            413 => "Content Too Large",
+            414 => "URI Too Long",
+            416 => "Range Not Satisfiable",
+            500 => "Internal Server Error",
+            501 => "Not Implemented",
+            503 => "Service Unavailable",
+            505 => "HTTP Version Not Supported",
+            _ => "Unknown",
         }
     }
 }
 
-impl ToString for ResponseType {
+impl ToString for StatusCode {
     fn to_string(&self) -> String {
-        let condition_code = self.condition_code();
-        let name = self.name();
-        format!("{} {}", condition_code, name)
+        format!("{} {}", self.0, self.default_reason_phrase())
     }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Response {
-    which: ResponseType,
+    which: StatusCode,
     http_version: HttpVersion,
     headers: Vec<Header>,
     data: Option<Vec<u8>>,
@@ -274,7 +744,7 @@ impl Response {
             .copied()
             .collect(),
             None => format!(
-                "{} {}\r\n{}",
+                "{} {}\r\n{}\r\n\r\n",
                 self.http_version,
                 self.which.to_string(),
                 headers,
@@ -284,10 +754,19 @@ impl Response {
         }
     }
 
+    pub fn raw(status: StatusCode, headers: Vec<Header>, data: Option<Vec<u8>>) -> Self {
+        Response {
+            which: status,
+            http_version: String::from(HTTP_VERSION),
+            headers,
+            data,
+        }
+    }
+
     pub fn serve_file(
         filename: &Path,
         content_type: ContentType,
-        response_type: ResponseType,
+        status: StatusCode,
     ) -> Result<Self> {
         let data = fs::read(filename)?;
 
@@ -297,7 +776,7 @@ impl Response {
         ];
 
         Ok(Response {
-            which: response_type,
+            which: status,
             http_version: String::from(HTTP_VERSION),
             headers,
             data: Some(data),
@@ -305,7 +784,7 @@ impl Response {
     }
 
     pub fn html_page(filename: &Path) -> Result<Self> {
-        Self::serve_file(filename, ContentType::html(), ResponseType::Ok)
+        Self::serve_file(filename, ContentType::html(), StatusCode::ok())
     }
 
     pub fn empty_internal_server_error() -> Self {
@@ -325,7 +804,7 @@ impl Response {
 
 </html>";
         Response {
-            which: ResponseType::InternalServerError,
+            which: StatusCode::internal_server_error(),
             http_version: format!("{}", HTTP_VERSION),
             headers: vec![
                 Header::ContentLength(html.len()),
@@ -343,4 +822,306 @@ impl Response {
             data: None,
         }
     }
+
+    /// Encode this response in the RFC 9292 known-length binary format. No
+    /// informational (1xx) blocks are emitted.
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(&encode_varint(1)); // framing: known-length response
+        out.extend_from_slice(&encode_varint(self.which.code() as u64));
+        out.extend_from_slice(&encode_field_section(&self.headers));
+        encode_byte_string(&mut out, self.data.as_deref().unwrap_or(&[]));
+        out.extend_from_slice(&encode_field_section(&[])); // empty trailers
+        out
+    }
+
+    /// Decode an RFC 9292 known-length binary response, skipping over any
+    /// leading informational (1xx) blocks.
+    pub fn parse_binary(buf: &[u8]) -> Result<Self> {
+        let mut pos = 0;
+        if read_varint(buf, &mut pos)? != 1 {
+            return Result::Err(parse_error(format!(
+                "Not a known-length binary response (wrong framing indicator)"
+            )));
+        }
+
+        let status = loop {
+            let code = read_varint(buf, &mut pos)?;
+            if (100..200).contains(&code) {
+                let _ = read_field_section(buf, &mut pos)?; // informational block
+                continue;
+            }
+            break code;
+        };
+
+        let headers = read_field_section(buf, &mut pos)?;
+        let content = read_byte_string(buf, &mut pos)?;
+        let _trailers = read_field_section(buf, &mut pos)?;
+
+        Ok(Response {
+            which: StatusCode(status as u16),
+            http_version: String::from(HTTP_VERSION),
+            headers,
+            data: Some(content),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn varint_round_trips_across_lengths() {
+        for value in [0u64, 63, 64, 16383, 16384, 1 << 29, 1 << 30] {
+            let encoded = encode_varint(value);
+            let mut pos = 0;
+            assert_eq!(read_varint(&encoded, &mut pos).unwrap(), value);
+            assert_eq!(pos, encoded.len());
+        }
+    }
+
+    #[test]
+    fn read_varint_rejects_truncation() {
+        let encoded = encode_varint(16384); // 4-byte varint
+        assert!(read_varint(&encoded[..2], &mut 0).is_err());
+    }
+
+    #[test]
+    fn request_binary_round_trip() {
+        let request = Request::raw(
+            "/submit",
+            HTTP_VERSION,
+            &[Header::Host(String::from("example.com"))],
+            RequestMethod::Post(b"hello".to_vec()),
+        );
+        let decoded = Request::parse_binary(&request.to_binary()).unwrap();
+        assert_eq!(decoded.uri(), "/submit");
+        assert!(matches!(decoded.request_method(), RequestMethod::Post(b) if b == b"hello"));
+    }
+
+    #[test]
+    fn response_binary_round_trip() {
+        let response = Response::raw(
+            StatusCode::ok(),
+            vec![Header::ContentType(ContentType::html())],
+            Some(b"<html></html>".to_vec()),
+        );
+        let decoded = Response::parse_binary(&response.to_binary()).unwrap();
+        assert_eq!(decoded, response);
+    }
+
+    #[test]
+    fn read_field_section_rejects_pair_overrunning_section_length() {
+        // Declares a 1-byte field section, but the first (name, value) pair
+        // actually consumes bytes past that declared length.
+        let buf = [0x01u8, 0x03, b'a', b'b', b'c', 0x01, b'1'];
+        let mut pos = 0;
+        assert!(read_field_section(&buf, &mut pos).is_err());
+    }
+
+    #[test]
+    fn parse_stream_reads_incrementally_across_short_reads() {
+        struct OneByteAtATime<'a>(&'a [u8]);
+        impl<'a> Read for OneByteAtATime<'a> {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                if self.0.is_empty() || buf.is_empty() {
+                    return Ok(0);
+                }
+                buf[0] = self.0[0];
+                self.0 = &self.0[1..];
+                Ok(1)
+            }
+        }
+
+        let raw = b"POST /submit HTTP/1.1\r\nHost: example.com\r\nContent-Length: 5\r\n\r\nhello";
+        let mut stream = OneByteAtATime(raw);
+        let request = Request::parse_stream(&mut stream).unwrap();
+        assert_eq!(request.uri(), "/submit");
+        assert!(matches!(request.request_method(), RequestMethod::Post(b) if b == b"hello"));
+    }
+
+    #[test]
+    fn parse_stream_with_limit_rejects_oversized_body() {
+        let raw = b"POST /submit HTTP/1.1\r\nHost: example.com\r\nContent-Length: 10\r\n\r\n0123456789";
+        let mut stream = &raw[..];
+        assert!(Request::parse_stream_with_limit(&mut stream, 4).is_err());
+    }
+
+    #[test]
+    fn parse_stream_rejects_unbounded_header_block() {
+        // A client that never sends the blank line terminating the headers
+        // must not be able to grow the header buffer without limit.
+        struct EndlessHeaderLines;
+        impl Read for EndlessHeaderLines {
+            fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+                let line = b"X-Pad: aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\r\n";
+                let n = line.len().min(buf.len());
+                buf[..n].copy_from_slice(&line[..n]);
+                Ok(n)
+            }
+        }
+
+        let mut stream = EndlessHeaderLines;
+        assert!(Request::parse_stream(&mut stream).is_err());
+    }
+
+    #[test]
+    fn status_code_default_reason_phrases() {
+        assert_eq!(StatusCode::ok().default_reason_phrase(), "OK");
+        assert_eq!(StatusCode::not_found().default_reason_phrase(), "Not Found");
+        assert_eq!(StatusCode(999).default_reason_phrase(), "Unknown");
+    }
+
+    #[test]
+    fn status_code_to_string_includes_reason_phrase() {
+        assert_eq!(StatusCode::created().to_string(), "201 Created");
+    }
+
+    #[test]
+    fn percent_decode_does_not_panic_on_stray_percent_before_multibyte_char() {
+        // A bare `%` followed by a multi-byte UTF-8 character must not be
+        // sliced at a raw byte offset that splits that character.
+        assert_eq!(percent_decode("%€", false), "%€");
+    }
+
+    #[test]
+    fn percent_decode_decodes_escapes_and_plus() {
+        assert_eq!(percent_decode("a%20b+c", true), "a b c");
+        assert_eq!(percent_decode("a%20b+c", false), "a b+c");
+    }
+
+    #[test]
+    fn parse_urlencoded_preserves_repeats_and_empty_values() {
+        let parsed = parse_urlencoded("a=1&a=2&b=&c");
+        assert_eq!(
+            parsed,
+            vec![
+                (String::from("a"), String::from("1")),
+                (String::from("a"), String::from("2")),
+                (String::from("b"), String::from("")),
+                (String::from("c"), String::from("")),
+            ]
+        );
+    }
+
+    #[test]
+    fn parsed_uri_splits_path_and_query() {
+        let request = Request::raw(
+            "/search?q=rust+web&q=again",
+            HTTP_VERSION,
+            &[],
+            RequestMethod::Get,
+        );
+        let parsed = request.parsed_uri();
+        assert_eq!(parsed.path, "/search");
+        assert_eq!(
+            parsed.query,
+            vec![
+                (String::from("q"), String::from("rust web")),
+                (String::from("q"), String::from("again")),
+            ]
+        );
+    }
+
+    #[test]
+    fn form_data_parses_post_body() {
+        let request = Request::raw(
+            "/submit",
+            HTTP_VERSION,
+            &[],
+            RequestMethod::Post(b"name=a+b&name=c".to_vec()),
+        );
+        assert_eq!(
+            request.form_data(),
+            vec![
+                (String::from("name"), String::from("a b")),
+                (String::from("name"), String::from("c")),
+            ]
+        );
+    }
+
+    #[test]
+    fn header_parses_typed_variants() {
+        assert_eq!(
+            Header::from_str("Host: example.com").unwrap(),
+            Header::Host(String::from("example.com"))
+        );
+        assert_eq!(
+            Header::from_str("Content-Length: 42").unwrap(),
+            Header::ContentLength(42)
+        );
+        assert_eq!(
+            Header::from_str("Content-Type: text/html; charset=utf-8").unwrap(),
+            Header::ContentType(ContentType::TextHTML(Some(Charset::Utf8)))
+        );
+        assert_eq!(
+            Header::from_str("Connection: keep-alive").unwrap(),
+            Header::Connection(String::from("keep-alive"))
+        );
+        assert_eq!(
+            Header::from_str("Accept: text/html").unwrap(),
+            Header::Accept(String::from("text/html"))
+        );
+        assert_eq!(
+            Header::from_str("User-Agent: curl/8.0").unwrap(),
+            Header::UserAgent(String::from("curl/8.0"))
+        );
+        assert_eq!(
+            Header::from_str("X-Custom: value").unwrap(),
+            Header::Other(String::from("X-Custom"), String::from("value"))
+        );
+    }
+
+    #[test]
+    fn header_falls_back_to_other_on_unparsable_content_length() {
+        assert_eq!(
+            Header::from_str("Content-Length: not-a-number").unwrap(),
+            Header::Other(String::from("Content-Length"), String::from("not-a-number"))
+        );
+    }
+
+    #[test]
+    fn header_falls_back_to_other_on_unrecognized_content_type() {
+        assert_eq!(
+            Header::from_str("Content-Type: application/json").unwrap(),
+            Header::Other(String::from("Content-Type"), String::from("application/json"))
+        );
+    }
+
+    #[test]
+    fn request_header_lookup_is_case_insensitive() {
+        let request = Request::raw(
+            "/",
+            HTTP_VERSION,
+            &[Header::Host(String::from("example.com"))],
+            RequestMethod::Get,
+        );
+        assert_eq!(
+            request.header("host"),
+            Some(&Header::Host(String::from("example.com")))
+        );
+    }
+
+    #[test]
+    fn to_raw_terminates_headers_with_blank_line_when_bodyless() {
+        let response = Response::raw(StatusCode::ok(), vec![Header::ContentLength(5)], None);
+        let raw = response.to_raw();
+        assert!(
+            raw.ends_with(b"\r\n\r\n"),
+            "expected header section to end in a blank line, got {:?}",
+            String::from_utf8_lossy(&raw)
+        );
+    }
+
+    #[test]
+    fn content_length_falls_back_to_non_canonically_cased_header() {
+        let request = Request::raw(
+            "/",
+            HTTP_VERSION,
+            &[Header::Other(String::from("content-length"), String::from("5"))],
+            RequestMethod::Get,
+        );
+        assert_eq!(request.content_length(), Some(5));
+    }
 }
@@ -1,28 +1,32 @@
 #![allow(clippy::unused_io_amount)]
 
 pub mod http;
-use http::{Request, Response};
+use http::{Request, RequestMethod, Response};
+
+/// A hook run on every outgoing response, given the request that produced it,
+/// for cross-cutting concerns such as injecting `Server`/`Date` headers.
+pub type PostHook = fn(&Request, Response) -> Response;
 
 pub mod error;
 use error::Result;
 
+pub mod static_files;
+
 mod threadpool;
 use threadpool::ThreadPool;
 
 use std::{
     io::prelude::*,
     net::{TcpListener, TcpStream},
-    str::from_utf8,
 };
 
-const BUFFER_SIZE: usize = 65536;
-
 #[derive(Debug)]
 pub struct WebServer<RequestHandle: Fn(Request) -> Result<Response>> {
     listener: TcpListener,
     workers: ThreadPool,
     internal_error_page: Response,
     handler: RequestHandle,
+    post_hook: Option<PostHook>,
 }
 
 impl<RequestHandle: Fn(Request) -> Result<Response>> WebServer<RequestHandle>
@@ -39,23 +43,46 @@ where
             workers: ThreadPool::new(worker_count),
             internal_error_page: Response::empty_internal_server_error(),
             handler,
+            post_hook: None,
         })
     }
 
     fn handle_connection(
         handler: RequestHandle,
+        post_hook: Option<PostHook>,
         mut stream: TcpStream,
         internal_error_page: Response,
     ) -> Result<()> {
-        //TODO: Make read_to_end + remove buffer
-        let mut buffer = [0; BUFFER_SIZE];
-        stream.read(&mut buffer).unwrap();
-
-        let request = Request::parse(from_utf8(&buffer)?)?;
+        let request = Request::parse_stream(&mut stream)?;
 
         println!("\n-----\n{:?}\n-----\n", request);
 
-        stream.write(&(handler)(request).unwrap_or(internal_error_page).to_raw())?;
+        // Captured before `request` is moved into `handler` below, so the
+        // post-hook doesn't force a clone of a potentially large POST body.
+        let request_for_hook = post_hook.map(|_| request.without_body());
+
+        // HEAD is routed through the same handler as the equivalent GET, then
+        // has its body stripped, so handlers never special-case HEAD.
+        let response = match request.request_method() {
+            RequestMethod::Head => {
+                let as_get = Request::raw(
+                    request.uri(),
+                    request.http_version(),
+                    request.headers(),
+                    RequestMethod::Get,
+                );
+                (handler)(as_get).map(|response| response.to_head())
+            }
+            _ => (handler)(request),
+        }
+        .unwrap_or(internal_error_page);
+
+        let response = match (post_hook, request_for_hook) {
+            (Some(hook), Some(request)) => hook(&request, response),
+            _ => response,
+        };
+
+        stream.write(&response.to_raw())?;
         stream.flush()?;
 
         Ok(())
@@ -65,14 +92,21 @@ where
         self.internal_error_page = response;
     }
 
+    /// Register a post-processing hook run on every outgoing response.
+    pub fn with_post_hook(mut self, hook: PostHook) -> Self {
+        self.post_hook = Some(hook);
+        self
+    }
+
     pub fn launch(self) {
         for stream in self.listener.incoming() {
             match stream {
                 Ok(stream) => {
                     let handler = self.handler.clone();
+                    let post_hook = self.post_hook;
                     let internal_error_page = self.internal_error_page.clone();
                     self.workers.execute(move || {
-                        Self::handle_connection(handler, stream, internal_error_page)
+                        Self::handle_connection(handler, post_hook, stream, internal_error_page)
                             .unwrap_or_else(|e| println!("Error on handling request: {}", e))
                     })
                 }
@@ -82,10 +116,74 @@ where
     }
 }
 
-/*
-No unit tests currently used
-
 #[cfg(test)]
 mod tests {
     use super::*;
-}*/
+    use http::{Header, StatusCode};
+    use std::net::TcpListener;
+
+    /// Feed `raw_request` through `handle_connection` over a real loopback
+    /// socket and return the raw response bytes.
+    fn roundtrip(
+        raw_request: &[u8],
+        handler: fn(Request) -> Result<Response>,
+        post_hook: Option<PostHook>,
+    ) -> Vec<u8> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        client.write_all(raw_request).unwrap();
+        client.shutdown(std::net::Shutdown::Write).unwrap();
+
+        let (server_stream, _) = listener.accept().unwrap();
+        WebServer::<fn(Request) -> Result<Response>>::handle_connection(
+            handler,
+            post_hook,
+            server_stream,
+            Response::empty_internal_server_error(),
+        )
+        .unwrap();
+
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn head_request_keeps_headers_but_strips_body() {
+        fn handler(_request: Request) -> Result<Response> {
+            let data = b"hello".to_vec();
+            Ok(Response::raw(
+                StatusCode::ok(),
+                vec![Header::ContentLength(data.len())],
+                Some(data),
+            ))
+        }
+
+        let raw = b"HEAD /report HTTP/1.1\r\nHost: example.com\r\n\r\n";
+        let response = String::from_utf8(roundtrip(raw, handler, None)).unwrap();
+
+        assert!(response.contains("Content-Length: 5"));
+        assert!(!response.contains("hello"));
+        // A bodyless response must still terminate its header section with
+        // the blank line, or a conformant client can't tell where it ends.
+        assert!(response.ends_with("\r\n\r\n"));
+    }
+
+    #[test]
+    fn post_hook_sees_request_metadata_without_the_body() {
+        fn handler(_request: Request) -> Result<Response> {
+            Ok(Response::raw(StatusCode::ok(), Vec::new(), None))
+        }
+        fn hook(request: &Request, response: Response) -> Response {
+            assert_eq!(request.uri(), "/submit");
+            assert!(matches!(
+                request.request_method(),
+                RequestMethod::Post(body) if body.is_empty()
+            ));
+            response
+        }
+
+        let raw = b"POST /submit HTTP/1.1\r\nHost: example.com\r\nContent-Length: 5\r\n\r\nhello";
+        roundtrip(raw, handler, Some(hook));
+    }
+}
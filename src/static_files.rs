@@ -0,0 +1,569 @@
+//! A file-serving handler for [`WebServer`](crate::WebServer).
+//!
+//! [`StaticFiles`] maps a request URI onto a root directory, inferring a MIME
+//! type from the file extension, auto-generating a directory index, and
+//! honoring conditional (`If-Modified-Since`/`If-None-Match`) and `Range`
+//! requests. Because it is `Copy`, it can be captured by a handler closure:
+//!
+//! ```ignore
+//! let files = StaticFiles::new("./public");
+//! WebServer::new("127.0.0.1:8080", 4, move |req| files.serve(&req))?.launch();
+//! ```
+
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    error::Result,
+    http::{percent_decode, Header, Response, StatusCode},
+};
+
+#[derive(Clone, Copy, Debug)]
+pub struct StaticFiles {
+    root: &'static Path,
+    list_directories: bool,
+}
+
+impl StaticFiles {
+    pub fn new(root: &'static str) -> Self {
+        StaticFiles {
+            root: Path::new(root),
+            list_directories: true,
+        }
+    }
+
+    /// Disable auto-generated directory index listings; directories then
+    /// resolve to `403 Forbidden`.
+    pub fn without_directory_listing(mut self) -> Self {
+        self.list_directories = false;
+        self
+    }
+
+    /// Serve the file (or directory) addressed by `request`.
+    pub fn serve(&self, request: &crate::http::Request) -> Result<Response> {
+        let path = request.uri().split('?').next().unwrap_or("");
+
+        let target = match self.resolve(path) {
+            Some(target) => target,
+            None => return Ok(forbidden()),
+        };
+
+        let metadata = match fs::metadata(&target) {
+            Ok(metadata) => metadata,
+            Err(_) => return Ok(not_found()),
+        };
+
+        // Lexical `..` rejection in `resolve` can't catch a symlink inside
+        // the served tree that points outside it; canonicalize and re-check
+        // before serving anything.
+        if !self.is_within_root(&target) {
+            return Ok(forbidden());
+        }
+
+        if metadata.is_dir() {
+            return self.directory_index(&target, path);
+        }
+
+        self.serve_file(request, &target, metadata.len())
+    }
+
+    /// Resolve a request path against the root, rejecting any component that
+    /// could escape it (`..`, path prefixes); a leading `/` and `.` are
+    /// skipped. Returns `None` on traversal.
+    fn resolve(&self, path: &str) -> Option<PathBuf> {
+        let decoded = percent_decode(path, false);
+        let mut target = self.root.to_path_buf();
+        for component in Path::new(&decoded).components() {
+            match component {
+                Component::Normal(part) => target.push(part),
+                // A request path is always root-relative (`/a/b`), so the
+                // leading `RootDir` component is expected, not an escape.
+                Component::CurDir | Component::RootDir => {}
+                Component::ParentDir | Component::Prefix(_) => return None,
+            }
+        }
+        Some(target)
+    }
+
+    /// Whether `target` canonicalizes to a path still rooted under
+    /// `self.root`, catching a symlink that escapes the served tree.
+    fn is_within_root(&self, target: &Path) -> bool {
+        let canonical_root = match fs::canonicalize(self.root) {
+            Ok(root) => root,
+            Err(_) => return false,
+        };
+        let canonical_target = match fs::canonicalize(target) {
+            Ok(target) => target,
+            Err(_) => return false,
+        };
+        canonical_target.starts_with(canonical_root)
+    }
+
+    fn serve_file(
+        &self,
+        request: &crate::http::Request,
+        target: &Path,
+        size: u64,
+    ) -> Result<Response> {
+        let modified = fs::metadata(target).ok().and_then(|m| m.modified().ok());
+        let etag = modified.map(|time| weak_etag(time, size));
+        let last_modified = modified.map(http_date);
+
+        // Conditional GET: `If-None-Match` matches any listed tag equal to the
+        // weak ETag; `If-Modified-Since` matches only when it equals the
+        // `Last-Modified` string exactly, as dates are not compared.
+        if let Some(etag) = &etag {
+            if let Some(requested) = header_value(request, "If-None-Match") {
+                if requested.split(',').any(|tag| tag.trim() == etag) {
+                    return Ok(not_modified(etag, &last_modified));
+                }
+            }
+        }
+        if let (Some(since), Some(last_modified)) =
+            (header_value(request, "If-Modified-Since"), &last_modified)
+        {
+            if since.trim() == last_modified {
+                return Ok(not_modified(etag.as_deref().unwrap_or(""), &Some(last_modified.clone())));
+            }
+        }
+
+        let data = fs::read(target)?;
+        let mime = mime_for(target);
+
+        if let Some(range) = header_value(request, "Range") {
+            return Ok(match parse_range(&range, size) {
+                Some((start, end)) => {
+                    let slice = data[start as usize..=end as usize].to_vec();
+                    let mut headers = file_headers(mime, slice.len(), &etag, &last_modified);
+                    headers.push(Header::Other(
+                        String::from("Content-Range"),
+                        format!("bytes {}-{}/{}", start, end, size),
+                    ));
+                    Response::raw(StatusCode::partial_content(), headers, Some(slice))
+                }
+                None => Response::raw(
+                    StatusCode(416),
+                    vec![Header::Other(
+                        String::from("Content-Range"),
+                        format!("bytes */{}", size),
+                    )],
+                    None,
+                ),
+            });
+        }
+
+        let headers = file_headers(mime, data.len(), &etag, &last_modified);
+        Ok(Response::raw(StatusCode::ok(), headers, Some(data)))
+    }
+
+    fn directory_index(&self, target: &Path, uri_path: &str) -> Result<Response> {
+        if !self.list_directories {
+            return Ok(forbidden());
+        }
+
+        let mut entries: Vec<String> = Vec::new();
+        for entry in fs::read_dir(target)? {
+            let name = entry?.file_name().to_string_lossy().into_owned();
+            let href = html_escape(&format!("{}/{}", uri_path.trim_end_matches('/'), name));
+            entries.push(format!(
+                "<li><a href=\"{}\">{}</a></li>",
+                href,
+                html_escape(&name)
+            ));
+        }
+        entries.sort();
+
+        let path = html_escape(uri_path);
+        let body = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n\n<head>\n    <meta charset=\"utf-8\">\n    \
+<title>Index of {path}</title>\n</head>\n\n<body>\n    <h1>Index of {path}</h1>\n    \
+<ul>\n{entries}\n    </ul>\n</body>\n\n</html>",
+            path = path,
+            entries = entries.join("\n")
+        );
+
+        let data = body.into_bytes();
+        let headers = vec![
+            Header::Other(String::from("Content-Type"), String::from("text/html")),
+            Header::ContentLength(data.len()),
+        ];
+        Ok(Response::raw(StatusCode::ok(), headers, Some(data)))
+    }
+}
+
+fn forbidden() -> Response {
+    Response::raw(StatusCode::forbidden(), Vec::new(), None)
+}
+
+fn not_found() -> Response {
+    Response::raw(StatusCode::not_found(), Vec::new(), None)
+}
+
+fn not_modified(etag: &str, last_modified: &Option<String>) -> Response {
+    let mut headers = Vec::new();
+    if !etag.is_empty() {
+        headers.push(Header::Other(String::from("ETag"), String::from(etag)));
+    }
+    if let Some(last_modified) = last_modified {
+        headers.push(Header::Other(
+            String::from("Last-Modified"),
+            last_modified.clone(),
+        ));
+    }
+    Response::raw(StatusCode::not_modified(), headers, None)
+}
+
+fn file_headers(
+    mime: &str,
+    length: usize,
+    etag: &Option<String>,
+    last_modified: &Option<String>,
+) -> Vec<Header> {
+    let mut headers = vec![
+        Header::Other(String::from("Content-Type"), String::from(mime)),
+        Header::ContentLength(length),
+        Header::Other(String::from("Accept-Ranges"), String::from("bytes")),
+    ];
+    if let Some(etag) = etag {
+        headers.push(Header::Other(String::from("ETag"), etag.clone()));
+    }
+    if let Some(last_modified) = last_modified {
+        headers.push(Header::Other(
+            String::from("Last-Modified"),
+            last_modified.clone(),
+        ));
+    }
+    headers
+}
+
+/// Escape text for safe interpolation into HTML, so that file names and
+/// request paths containing `<`, `>`, `&`, or `"` can't break markup or
+/// attribute quoting in the auto-generated directory index.
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Look up a catch-all `Header::Other` request header by name,
+/// case-insensitively. Typed headers such as `Host` are not searched.
+fn header_value(request: &crate::http::Request, name: &str) -> Option<String> {
+    request.headers().iter().find_map(|header| match header {
+        Header::Other(key, value) if key.eq_ignore_ascii_case(name) => Some(value.clone()),
+        _ => None,
+    })
+}
+
+/// A weak ETag derived from the file's modification time and size.
+fn weak_etag(modified: SystemTime, size: u64) -> String {
+    let secs = modified
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format!("W/\"{}-{}\"", secs, size)
+}
+
+/// Parse a single `Range: bytes=start-end` spec into inclusive bounds clamped
+/// to `[0, size)`. Returns `None` for an unsatisfiable or malformed range.
+fn parse_range(header: &str, size: u64) -> Option<(u64, u64)> {
+    let spec = header.trim().strip_prefix("bytes=")?;
+    let (start, end) = spec.split_once('-')?;
+    if size == 0 {
+        return None;
+    }
+    let (start, end) = if start.is_empty() {
+        let suffix: u64 = end.trim().parse().ok()?;
+        if suffix == 0 {
+            return None;
+        }
+        (size - suffix.min(size), size - 1)
+    } else {
+        let start: u64 = start.trim().parse().ok()?;
+        let end = match end.trim() {
+            "" => size - 1,
+            end => end.parse::<u64>().ok()?.min(size - 1),
+        };
+        (start, end)
+    };
+    if start > end || start >= size {
+        return None;
+    }
+    Some((start, end))
+}
+
+/// Format a `SystemTime` as an RFC 1123 / IMF-fixdate string for
+/// `Last-Modified`.
+fn http_date(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let days = (secs / 86_400) as i64;
+    let seconds_of_day = secs % 86_400;
+
+    let (hour, minute, second) = (
+        seconds_of_day / 3600,
+        (seconds_of_day % 3600) / 60,
+        seconds_of_day % 60,
+    );
+
+    // Civil date from days since the Unix epoch (Howard Hinnant's algorithm).
+    let weekday = ((days % 7 + 4) % 7 + 7) % 7; // 1970-01-01 was a Thursday
+    let z = days + 719_468;
+    let era = if z >= 0 { z } else { z - 146_096 } / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let year = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = if month <= 2 { year + 1 } else { year };
+
+    const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} GMT",
+        WEEKDAYS[weekday as usize],
+        day,
+        MONTHS[(month - 1) as usize],
+        year,
+        hour,
+        minute,
+        second
+    )
+}
+
+/// Infer a MIME type from a file's extension.
+fn mime_for(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("html") | Some("htm") => "text/html",
+        Some("css") => "text/css",
+        Some("js") | Some("mjs") => "text/javascript",
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        Some("txt") => "text/plain",
+        Some("csv") => "text/csv",
+        Some("wasm") => "application/wasm",
+        Some("pdf") => "application/pdf",
+        Some("zip") => "application/zip",
+        Some("gz") => "application/gzip",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("ico") => "image/x-icon",
+        Some("bmp") => "image/bmp",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("ttf") => "font/ttf",
+        Some("otf") => "font/otf",
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        Some("ogg") => "audio/ogg",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{Request, RequestMethod};
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// A scratch directory under the system temp dir, removed on drop. The
+    /// root is leaked to a `'static str` because [`StaticFiles::new`] expects
+    /// one, which is fine for the life of a single test.
+    struct TempRoot(PathBuf);
+
+    impl TempRoot {
+        fn new() -> Self {
+            static COUNTER: AtomicU32 = AtomicU32::new(0);
+            let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+            let dir = std::env::temp_dir().join(format!(
+                "web_static_files_test_{}_{}",
+                std::process::id(),
+                n
+            ));
+            fs::create_dir_all(&dir).unwrap();
+            TempRoot(dir)
+        }
+
+        fn write(&self, relative: &str, contents: &[u8]) {
+            let path = self.0.join(relative);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+
+        fn static_files(&self) -> StaticFiles {
+            let root: &'static str = Box::leak(self.0.to_str().unwrap().to_owned().into_boxed_str());
+            StaticFiles::new(root)
+        }
+    }
+
+    impl Drop for TempRoot {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn get(uri: &str) -> Request {
+        Request::raw(uri, crate::http::HTTP_VERSION, &[], RequestMethod::Get)
+    }
+
+    #[test]
+    fn resolve_rejects_parent_dir_traversal() {
+        let root = TempRoot::new();
+        let files = root.static_files();
+        assert!(files.resolve("/../../etc/passwd").is_none());
+        assert!(files.resolve("/a/../../b").is_none());
+    }
+
+    #[test]
+    fn resolve_joins_normal_components_under_root() {
+        let root = TempRoot::new();
+        let files = root.static_files();
+        let resolved = files.resolve("/a/b.txt").unwrap();
+        assert_eq!(resolved, root.0.join("a").join("b.txt"));
+    }
+
+    #[test]
+    fn serve_returns_not_found_for_missing_file() {
+        let root = TempRoot::new();
+        let files = root.static_files();
+        let response = files.serve(&get("/missing.txt")).unwrap();
+        assert_eq!(response.to_raw(), not_found().to_raw());
+    }
+
+    #[test]
+    fn error_responses_terminate_headers_with_blank_line() {
+        let root = TempRoot::new();
+        let files = root.static_files();
+        let response = files.serve(&get("/missing.txt")).unwrap();
+        assert!(response.to_raw().ends_with(b"\r\n\r\n"));
+    }
+
+    #[test]
+    fn serve_rejects_symlink_escaping_the_root() {
+        #[cfg(unix)]
+        {
+            let root = TempRoot::new();
+            let outside = TempRoot::new();
+            outside.write("secret.txt", b"top secret");
+            std::os::unix::fs::symlink(outside.0.join("secret.txt"), root.0.join("escape"))
+                .unwrap();
+
+            let files = root.static_files();
+            let response = files.serve(&get("/escape")).unwrap();
+            assert_eq!(response.to_raw(), forbidden().to_raw());
+        }
+    }
+
+    #[test]
+    fn serve_returns_full_file_with_ranges_header() {
+        let root = TempRoot::new();
+        root.write("hello.txt", b"hello world");
+        let files = root.static_files();
+        let response = files.serve(&get("/hello.txt")).unwrap();
+        let raw = String::from_utf8_lossy(&response.to_raw()).into_owned();
+        assert!(raw.contains("200 OK"));
+        assert!(raw.contains("Accept-Ranges: bytes"));
+        assert!(raw.ends_with("hello world"));
+    }
+
+    #[test]
+    fn serve_honors_partial_range_request() {
+        let root = TempRoot::new();
+        root.write("hello.txt", b"hello world");
+        let files = root.static_files();
+        let mut request = get("/hello.txt");
+        request = Request::raw(
+            request.uri(),
+            request.http_version(),
+            &[Header::Other(String::from("Range"), String::from("bytes=0-4"))],
+            RequestMethod::Get,
+        );
+        let response = files.serve(&request).unwrap();
+        let raw = String::from_utf8_lossy(&response.to_raw()).into_owned();
+        assert!(raw.contains("206 Partial Content"));
+        assert!(raw.contains("Content-Range: bytes 0-4/11"));
+        assert!(raw.ends_with("hello"));
+    }
+
+    #[test]
+    fn serve_rejects_unsatisfiable_range_request() {
+        let root = TempRoot::new();
+        root.write("hello.txt", b"hello world");
+        let files = root.static_files();
+        let request = Request::raw(
+            "/hello.txt",
+            crate::http::HTTP_VERSION,
+            &[Header::Other(
+                String::from("Range"),
+                String::from("bytes=100-200"),
+            )],
+            RequestMethod::Get,
+        );
+        let response = files.serve(&request).unwrap();
+        let raw = String::from_utf8_lossy(&response.to_raw()).into_owned();
+        assert!(raw.contains("416"));
+        assert!(raw.contains("Content-Range: bytes */11"));
+    }
+
+    #[test]
+    fn serve_returns_not_modified_for_matching_etag() {
+        let root = TempRoot::new();
+        root.write("hello.txt", b"hello world");
+        let files = root.static_files();
+        let first = files.serve(&get("/hello.txt")).unwrap();
+        let etag = first
+            .to_raw()
+            .windows(5)
+            .position(|w| w == b"ETag:")
+            .map(|pos| {
+                let rest = &first.to_raw()[pos + 6..];
+                let end = rest.windows(2).position(|w| w == b"\r\n").unwrap();
+                String::from_utf8_lossy(&rest[..end]).into_owned()
+            })
+            .unwrap();
+
+        let conditional = Request::raw(
+            "/hello.txt",
+            crate::http::HTTP_VERSION,
+            &[Header::Other(String::from("If-None-Match"), etag)],
+            RequestMethod::Get,
+        );
+        let response = files.serve(&conditional).unwrap();
+        assert!(String::from_utf8_lossy(&response.to_raw()).contains("304 Not Modified"));
+    }
+
+    #[test]
+    fn directory_index_escapes_html_in_file_names_and_path() {
+        let root = TempRoot::new();
+        root.write("weird<dir/<script>.txt", b"x");
+        let files = root.static_files();
+        let response = files.serve(&get("/weird<dir")).unwrap();
+        let raw = String::from_utf8_lossy(&response.to_raw()).into_owned();
+        assert!(!raw.contains("<script>.txt"));
+        assert!(raw.contains("&lt;script&gt;.txt"));
+        assert!(!raw.contains("Index of /weird<dir<"));
+        assert!(raw.contains("Index of /weird&lt;dir"));
+    }
+}